@@ -0,0 +1,152 @@
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+use tokio::sync::Semaphore;
+
+use crate::commands::{convert_one, ConvertOptions};
+use crate::encoders::{OutputBits, OutputFormat};
+use crate::loudness::Normalization;
+use crate::CancelFlag;
+
+const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "mp3", "flac", "ogg", "m4a", "aac", "wma", "aiff"];
+
+/// Either takes the caller's explicit file list, or scans `directory` (non-
+/// recursively) for files with a supported extension, sorted for
+/// deterministic batch ordering.
+fn resolve_batch_files(file_paths: Option<Vec<String>>, directory: Option<String>) -> Result<Vec<String>, String> {
+    if let Some(paths) = file_paths {
+        return Ok(paths);
+    }
+
+    let directory = directory.ok_or("Either file_paths or directory must be provided")?;
+    let entries = std::fs::read_dir(&directory).map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let is_supported = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        if path.is_file() && is_supported {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[tauri::command]
+pub fn convert_batch(
+    app: tauri::AppHandle,
+    state: tauri::State<CancelFlag>,
+    file_paths: Option<Vec<String>>,
+    directory: Option<String>,
+    output_format: Option<OutputFormat>,
+    mp3_bitrate_kbps: Option<u32>,
+    target_sample_rate: Option<u32>,
+    max_sample_rate: Option<u32>,
+    include_lfe: Option<bool>,
+    output_bits: Option<OutputBits>,
+    copy_metadata: Option<bool>,
+    normalize: Option<Normalization>,
+    max_concurrency: Option<usize>,
+) -> Result<(), String> {
+    let files = resolve_batch_files(file_paths, directory)?;
+
+    {
+        let mut cancel = state.0.lock().unwrap();
+        *cancel = false;
+    }
+    let cancel_flag = state.0.clone();
+    let app_clone = app.clone();
+    let opts = ConvertOptions {
+        output_format: output_format.unwrap_or_default(),
+        mp3_bitrate_kbps: mp3_bitrate_kbps.unwrap_or(192),
+        target_sample_rate,
+        max_sample_rate,
+        include_lfe: include_lfe.unwrap_or(false),
+        output_bits,
+        copy_metadata: copy_metadata.unwrap_or(true),
+        normalize,
+    };
+    let concurrency = max_concurrency.unwrap_or_else(|| {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
+    });
+
+    tauri::async_runtime::spawn(async move {
+        let semaphore = Arc::new(Semaphore::new(concurrency));
+        let succeeded = Arc::new(Mutex::new(0usize));
+        let failed = Arc::new(Mutex::new(0usize));
+        let cancelled = Arc::new(Mutex::new(0usize));
+
+        let mut handles = Vec::with_capacity(files.len());
+        for (index, path) in files.into_iter().enumerate() {
+            let semaphore = semaphore.clone();
+            let app_clone = app_clone.clone();
+            let cancel_flag = cancel_flag.clone();
+            let succeeded = succeeded.clone();
+            let failed = failed.clone();
+            let cancelled = cancelled.clone();
+
+            let handle = tauri::async_runtime::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("semaphore not closed");
+
+                if *cancel_flag.lock().unwrap() {
+                    *cancelled.lock().unwrap() += 1;
+                    let _ = app_clone.emit("batch-file-result", serde_json::json!({
+                        "index": index, "path": path, "success": false, "error": "Conversion cancelled",
+                    }));
+                    return;
+                }
+
+                let result = convert_one(&path, &opts, &cancel_flag, |progress| {
+                    let _ = app_clone.emit("batch-progress", serde_json::json!({
+                        "index": index, "path": path, "progress": progress,
+                    }));
+                });
+
+                match result {
+                    Ok(outcome) => {
+                        *succeeded.lock().unwrap() += 1;
+                        let _ = app_clone.emit("batch-file-result", serde_json::json!({
+                            "index": index,
+                            "path": path,
+                            "success": true,
+                            "outputPath": outcome.output_path,
+                            "downmixMatrix": outcome.downmix_matrix,
+                            "lfeDropped": outcome.lfe_dropped,
+                            "measuredLoudness": outcome.measured_loudness,
+                            "appliedGainDb": outcome.applied_gain_db,
+                        }));
+                    }
+                    Err(e) if e == "Conversion cancelled" => {
+                        *cancelled.lock().unwrap() += 1;
+                        let _ = app_clone.emit("batch-file-result", serde_json::json!({
+                            "index": index, "path": path, "success": false, "error": e,
+                        }));
+                    }
+                    Err(e) => {
+                        *failed.lock().unwrap() += 1;
+                        let _ = app_clone.emit("batch-file-result", serde_json::json!({
+                            "index": index, "path": path, "success": false, "error": e,
+                        }));
+                    }
+                }
+            });
+            handles.push(handle);
+        }
+
+        for handle in handles {
+            let _ = handle.await;
+        }
+
+        let _ = app_clone.emit("batch-result", serde_json::json!({
+            "succeeded": *succeeded.lock().unwrap(),
+            "failed": *failed.lock().unwrap(),
+            "cancelled": *cancelled.lock().unwrap(),
+        }));
+    });
+
+    Ok(())
+}
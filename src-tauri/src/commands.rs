@@ -0,0 +1,414 @@
+use std::io::{Read, Seek};
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+
+use crate::downmix::DownmixMatrix;
+use crate::encoders::{self, OutputBits, OutputFormat};
+use crate::loudness::{LoudnessAnalyzer, Normalization};
+use crate::metadata;
+use crate::resample::{self, MonoResampler};
+use crate::CancelFlag;
+
+struct ProgressReader<R: Read + Seek + Send + Sync> {
+    inner: R,
+    bytes_read: Arc<Mutex<u64>>,
+    total_size: u64,
+}
+
+impl<R: Read + Seek + Send + Sync> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let res = self.inner.read(buf);
+        if let Ok(n) = res {
+            *self.bytes_read.lock().unwrap() += n as u64;
+        }
+        res
+    }
+}
+
+impl<R: Read + Seek + Send + Sync> Seek for ProgressReader<R> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl<R> symphonia::core::io::MediaSource for ProgressReader<R> where R: Read + Seek + Send + Sync {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.total_size)
+    }
+}
+
+#[derive(serde::Serialize)]
+struct AudioInfo {
+    channels: u32,
+    sample_rate: u32,
+    bits_per_sample: u32,
+    duration_seconds: Option<f64>,
+    output_sample_rate: u32,
+    will_resample: bool,
+}
+
+#[derive(serde::Serialize)]
+struct GetAudioInfoResponse {
+    success: bool,
+    data: Option<AudioInfo>,
+    error: Option<String>,
+}
+
+#[tauri::command]
+pub fn cancel_conversion(state: tauri::State<CancelFlag>) {
+    *state.0.lock().unwrap() = true;
+}
+
+#[tauri::command]
+pub fn get_audio_info(
+    file_path: String,
+    target_sample_rate: Option<u32>,
+    max_sample_rate: Option<u32>,
+) -> GetAudioInfoResponse {
+    use std::fs::File;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    match (|| -> Result<AudioInfo, String> {
+        let file = File::open(&file_path).map_err(|e| format!("Failed to open file: {}", e))?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let hint = Hint::new();
+        let format_opts = FormatOptions::default();
+        let metadata_opts = MetadataOptions::default();
+        let probed = symphonia::default::get_probe().format(&hint, mss, &format_opts, &metadata_opts)
+            .map_err(|e| format!("Unsupported format: {}", e))?;
+
+        let format = probed.format;
+
+        let track = format.tracks().iter().find(|t| t.codec_params.codec != symphonia::core::codecs::CODEC_TYPE_NULL)
+            .ok_or("No supported audio tracks")?;
+
+        let channels = track.codec_params.channels.map(|c| c.count() as u32).unwrap_or(0);
+        let sample_rate = track.codec_params.sample_rate.ok_or("Unknown sample rate")?;
+        let bits_per_sample = track.codec_params.bits_per_sample.unwrap_or(16);
+
+        let duration_seconds = track.codec_params.n_frames.map(|n| n as f64 / sample_rate as f64);
+        let output_sample_rate =
+            resample::target_rate_for(sample_rate, target_sample_rate, max_sample_rate)
+                .unwrap_or(sample_rate);
+
+        Ok(AudioInfo {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            duration_seconds,
+            output_sample_rate,
+            will_resample: output_sample_rate != sample_rate,
+        })
+    })() {
+        Ok(info) => GetAudioInfoResponse {
+            success: true,
+            data: Some(info),
+            error: None,
+        },
+        Err(e) => GetAudioInfoResponse {
+            success: false,
+            data: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// Knobs shared by a single conversion, whether it's run standalone from
+/// `convert_to_mono` or as one item of a `convert_batch` run.
+#[derive(Clone, Copy)]
+pub(crate) struct ConvertOptions {
+    pub(crate) output_format: OutputFormat,
+    pub(crate) mp3_bitrate_kbps: u32,
+    pub(crate) target_sample_rate: Option<u32>,
+    pub(crate) max_sample_rate: Option<u32>,
+    pub(crate) include_lfe: bool,
+    pub(crate) output_bits: Option<OutputBits>,
+    pub(crate) copy_metadata: bool,
+    pub(crate) normalize: Option<Normalization>,
+}
+
+pub(crate) struct ConvertOutcome {
+    pub(crate) output_path: String,
+    pub(crate) downmix_matrix: &'static str,
+    pub(crate) lfe_dropped: bool,
+    pub(crate) measured_loudness: Option<f64>,
+    pub(crate) applied_gain_db: Option<f64>,
+}
+
+/// Decodes `file_path`, downmixes it to mono and writes the result
+/// according to `opts`. `on_progress` is called with a 0-100 percentage as
+/// decoding proceeds; `cancel_flag` is polled between packets so callers
+/// (single-file or batch) can abort it from outside.
+pub(crate) fn convert_one(
+    file_path: &str,
+    opts: &ConvertOptions,
+    cancel_flag: &Arc<Mutex<bool>>,
+    mut on_progress: impl FnMut(f64),
+) -> Result<ConvertOutcome, String> {
+    use std::fs::File;
+    use symphonia::core::audio::Signal;
+    use symphonia::core::codecs::DecoderOptions;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    let file = File::open(file_path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let total_size = file
+        .metadata()
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?
+        .len();
+    let bytes_read = Arc::new(Mutex::new(0u64));
+    let progress_reader = ProgressReader { inner: file, bytes_read: bytes_read.clone(), total_size };
+    let mss = MediaSourceStream::new(Box::new(progress_reader), Default::default());
+
+    let hint = Hint::new();
+    let format_opts = FormatOptions::default();
+    let metadata_opts = MetadataOptions::default();
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &format_opts, &metadata_opts)
+        .map_err(|e| format!("Unsupported format: {}", e))?;
+
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != symphonia::core::codecs::CODEC_TYPE_NULL)
+        .ok_or("No supported audio tracks")?;
+
+    let track_id = track.id;
+    let sample_rate = track.codec_params.sample_rate.ok_or("Unknown sample rate")?;
+    let source_bits = track.codec_params.bits_per_sample.unwrap_or(16);
+    let output_bits = opts.output_bits.unwrap_or_else(|| OutputBits::nearest(source_bits));
+
+    let tags = opts.copy_metadata.then(|| metadata::read_tags(&mut format));
+
+    let dec_opts = DecoderOptions::default();
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &dec_opts)
+        .map_err(|e| format!("Unsupported codec: {}", e))?;
+
+    // Resample only if the caller asked for a target rate, or the source
+    // exceeds the configured cap
+    let target_rate = resample::target_rate_for(sample_rate, opts.target_sample_rate, opts.max_sample_rate);
+    let output_rate = target_rate.unwrap_or(sample_rate);
+    let mut resampler = match target_rate {
+        Some(dst_rate) => Some(MonoResampler::new(sample_rate, dst_rate)?),
+        None => None,
+    };
+
+    // Prepare the output encoder for the requested format
+    let stem = file_path
+        .trim_end_matches(".wav")
+        .trim_end_matches(".mp3")
+        .trim_end_matches(".flac")
+        .trim_end_matches(".ogg");
+    let output_path = format!("{}_mono.{}", stem, opts.output_format.extension());
+    let mut encoder = encoders::create_encoder(
+        opts.output_format,
+        &output_path,
+        output_rate,
+        opts.mp3_bitrate_kbps,
+        output_bits,
+        tags.as_ref(),
+    )?;
+
+    // If normalization was requested, run a silent first pass to measure the
+    // source's loudness (peak or EBU R128 integrated), then seek the
+    // (seekable) stream back to the start and reset the decoder so the real
+    // write pass below decodes it again from scratch with the derived gain
+    // applied.
+    let mut gain: f32 = 1.0;
+    let mut measured_loudness: Option<f64> = None;
+    let mut applied_gain_db: Option<f64> = None;
+    if let Some(mode) = opts.normalize {
+        let mut analyzer = LoudnessAnalyzer::new(mode, sample_rate);
+        let mut analysis_matrix: Option<DownmixMatrix> = None;
+        loop {
+            if *cancel_flag.lock().unwrap() {
+                let _ = std::fs::remove_file(&output_path);
+                return Err("Conversion cancelled".to_string());
+            }
+
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                Err(symphonia::core::errors::Error::ResetRequired) => continue,
+                Err(_) => break,
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+
+            let decoded = decoder.decode(&packet).map_err(|e| format!("Decode error: {}", e))?;
+            if analysis_matrix.is_none() {
+                analysis_matrix = Some(DownmixMatrix::build(decoded.spec().channels, opts.include_lfe));
+            }
+            let mono = crate::downmix::downmix_any(decoded, analysis_matrix.as_ref().unwrap());
+            analyzer.push(&mono);
+        }
+
+        let (result, resolved_gain) = analyzer.resolve(mode);
+        gain = resolved_gain;
+        measured_loudness = Some(result.measured_loudness);
+        applied_gain_db = Some(result.applied_gain_db);
+
+        format
+            .seek(
+                symphonia::core::formats::SeekMode::Accurate,
+                symphonia::core::formats::SeekTo::TimeStamp { ts: 0, track_id },
+            )
+            .map_err(|e| format!("Failed to rewind for write pass: {}", e))?;
+        decoder.reset();
+        // The analysis pass above already read the whole file through
+        // `bytes_read`, so without resetting it the write pass's progress
+        // would start at ~100% instead of 0%.
+        *bytes_read.lock().unwrap() = 0;
+    }
+
+    let mut packet_count = 0;
+    let mut matrix: Option<DownmixMatrix> = None;
+    loop {
+        if *cancel_flag.lock().unwrap() {
+            let _ = std::fs::remove_file(&output_path);
+            return Err("Conversion cancelled".to_string());
+        }
+
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(symphonia::core::errors::Error::ResetRequired) => continue,
+            Err(_) => break,
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        packet_count += 1;
+        if packet_count % 100 == 0 {
+            let current_bytes = *bytes_read.lock().unwrap();
+            on_progress(current_bytes as f64 / total_size as f64 * 100.0);
+        }
+
+        let decoded = decoder.decode(&packet).map_err(|e| format!("Decode error: {}", e))?;
+
+        // Resolve the downmix matrix once, from the first decoded buffer's
+        // channel layout
+        if matrix.is_none() {
+            matrix = Some(DownmixMatrix::build(decoded.spec().channels, opts.include_lfe));
+        }
+        let matrix = matrix.as_ref().unwrap();
+
+        // Downmix to mono, normalized to [-1.0, 1.0] so it can feed the
+        // resampler and encoder without losing precision
+        let mut mono = crate::downmix::downmix_any(decoded, matrix);
+        if gain != 1.0 {
+            for s in mono.iter_mut() {
+                *s *= gain;
+            }
+        }
+
+        let ready = match resampler.as_mut() {
+            Some(r) => {
+                r.push(&mono)?;
+                r.drain()
+            }
+            None => mono,
+        };
+
+        encoder.write_samples(&ready)?;
+    }
+
+    if let Some(r) = resampler.take() {
+        encoder.write_samples(&r.finish()?)?;
+    }
+
+    encoder.finalize()?;
+
+    // MP3 tags are embedded while encoding and Vorbis output carries no
+    // tags (see `VorbisEncoder::create`); WAV/FLAC need a post-process pass
+    // since their encoders here don't expose tag writing directly
+    if let Some(tags) = &tags {
+        match opts.output_format {
+            OutputFormat::Wav => metadata::append_wav_info_chunk(&output_path, tags)?,
+            OutputFormat::Flac => metadata::write_flac_tags(&output_path, tags)?,
+            OutputFormat::Mp3 | OutputFormat::Vorbis => {}
+        }
+    }
+
+    let (downmix_matrix, lfe_dropped) = matrix
+        .as_ref()
+        .map(|m| (m.name, m.lfe_dropped))
+        .unwrap_or(("equal-weight", false));
+
+    Ok(ConvertOutcome { output_path, downmix_matrix, lfe_dropped, measured_loudness, applied_gain_db })
+}
+
+#[tauri::command]
+pub fn convert_to_mono(
+    app: tauri::AppHandle,
+    state: tauri::State<CancelFlag>,
+    file_path: String,
+    output_format: Option<OutputFormat>,
+    mp3_bitrate_kbps: Option<u32>,
+    target_sample_rate: Option<u32>,
+    max_sample_rate: Option<u32>,
+    include_lfe: Option<bool>,
+    output_bits: Option<OutputBits>,
+    copy_metadata: Option<bool>,
+    normalize: Option<Normalization>,
+) -> Result<(), String> {
+    {
+        let mut cancel = state.0.lock().unwrap();
+        *cancel = false;
+    }
+    let cancel_flag = state.0.clone();
+    let app_clone = app.clone();
+    let opts = ConvertOptions {
+        output_format: output_format.unwrap_or_default(),
+        mp3_bitrate_kbps: mp3_bitrate_kbps.unwrap_or(192),
+        target_sample_rate,
+        max_sample_rate,
+        include_lfe: include_lfe.unwrap_or(false),
+        output_bits,
+        copy_metadata: copy_metadata.unwrap_or(true),
+        normalize,
+    };
+
+    tauri::async_runtime::spawn(async move {
+        println!("Converting file: {}", file_path);
+        let _ = app_clone.emit("progress", "Starting conversion...");
+
+        let result = convert_one(&file_path, &opts, &cancel_flag, |progress| {
+            println!("Progress: {:.1}%", progress);
+            let _ = app_clone.emit("progress", format!("{:.1}%", progress));
+        });
+
+        match result {
+            Ok(outcome) => {
+                let _ = app_clone.emit("progress", "Conversion complete.");
+                let _ = app_clone.emit("conversion-result", serde_json::json!({
+                    "success": true,
+                    "message": format!("Converted to mono: {}", outcome.output_path),
+                    "downmixMatrix": outcome.downmix_matrix,
+                    "lfeDropped": outcome.lfe_dropped,
+                    "measuredLoudness": outcome.measured_loudness,
+                    "appliedGainDb": outcome.applied_gain_db,
+                }));
+            }
+            Err(e) => {
+                let _ = app_clone.emit("conversion-result", serde_json::json!({ "success": false, "error": e }));
+            }
+        }
+    });
+
+    Ok(())
+}
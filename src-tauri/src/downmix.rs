@@ -0,0 +1,170 @@
+use symphonia::core::audio::{AudioBuffer, AudioBufferRef, Channels, Signal};
+use symphonia::core::conv::IntoSample;
+use symphonia::core::sample::Sample;
+
+const CENTER_COEFF: f32 = std::f32::consts::FRAC_1_SQRT_2; // ~0.707, -3dB
+const SURROUND_COEFF: f32 = std::f32::consts::FRAC_1_SQRT_2;
+const LFE_BASE_COEFF: f32 = 1.0;
+const LFE_ATTENUATION: f32 = 0.316; // ~ -10dB, applied on top of LFE_BASE_COEFF below
+
+/// One entry per channel role this matrix knows how to weight. Bit order
+/// matches the order symphonia exposes channels in (`AudioBuffer::chan`),
+/// so layouts are only recognized when every channel present is one of
+/// these roles.
+const KNOWN_ROLES: &[(Channels, f32, bool)] = &[
+    (Channels::FRONT_LEFT, 1.0, false),
+    (Channels::FRONT_RIGHT, 1.0, false),
+    (Channels::FRONT_CENTRE, CENTER_COEFF, false),
+    (Channels::LFE1, LFE_BASE_COEFF, true),
+    (Channels::REAR_LEFT, SURROUND_COEFF, false),
+    (Channels::REAR_RIGHT, SURROUND_COEFF, false),
+    (Channels::SIDE_LEFT, SURROUND_COEFF, false),
+    (Channels::SIDE_RIGHT, SURROUND_COEFF, false),
+];
+
+/// A downmix matrix resolved for a specific channel layout: per-channel
+/// coefficients (in the order `AudioBuffer::chan` yields them) plus the
+/// bookkeeping `convert_to_mono` reports back to the frontend.
+pub(crate) struct DownmixMatrix {
+    coefficients: Vec<f32>,
+    coefficient_sum: f32,
+    pub(crate) name: &'static str,
+    pub(crate) lfe_dropped: bool,
+}
+
+impl DownmixMatrix {
+    /// Builds the ITU-R BS.775 matrix for a recognized 5.1/7.1-style layout
+    /// (front centre + LFE, plus any of front L/R and the rear and side
+    /// surrounds), or falls back to the equal-weight average for anything
+    /// else.
+    pub(crate) fn build(channels: Channels, include_lfe: bool) -> Self {
+        let recognized: Vec<(Channels, f32, bool)> = KNOWN_ROLES
+            .iter()
+            .copied()
+            .filter(|(flag, _, _)| channels.contains(*flag))
+            .collect();
+
+        let is_recognized_surround_layout = channels.contains(Channels::FRONT_CENTRE)
+            && channels.contains(Channels::LFE1)
+            && recognized.len() == channels.count();
+
+        if !is_recognized_surround_layout {
+            let n = channels.count().max(1);
+            return Self {
+                coefficients: vec![1.0; n],
+                coefficient_sum: n as f32,
+                name: "equal-weight",
+                lfe_dropped: false,
+            };
+        }
+
+        let mut coefficients = Vec::with_capacity(recognized.len());
+        let mut coefficient_sum = 0.0;
+        let mut lfe_dropped = false;
+        for (_, coeff, is_lfe) in &recognized {
+            let applied = if *is_lfe {
+                lfe_dropped = !include_lfe;
+                if include_lfe { coeff * LFE_ATTENUATION } else { 0.0 }
+            } else {
+                *coeff
+            };
+            coefficients.push(applied);
+            coefficient_sum += applied;
+        }
+
+        Self {
+            coefficients,
+            coefficient_sum,
+            name: "itu-r-bs775",
+            lfe_dropped,
+        }
+    }
+
+    /// Applies the matrix to one frame's per-channel samples, normalizing by
+    /// the sum of applied coefficients so the mix doesn't clip.
+    fn apply(&self, frame: &[f32]) -> f32 {
+        if self.coefficient_sum <= 0.0 {
+            return 0.0;
+        }
+        let weighted: f32 = frame.iter().zip(&self.coefficients).map(|(s, c)| s * c).sum();
+        weighted / self.coefficient_sum
+    }
+}
+
+/// Downmixes every frame of a decoded buffer to mono using `matrix`,
+/// converting each channel's native sample format to `f32` via symphonia's
+/// sample conversion (so e.g. `u8`'s 128 midpoint and `i32`'s full range are
+/// handled the same way symphonia handles them everywhere else).
+pub(crate) fn downmix_buffer<S>(buf: &AudioBuffer<S>, matrix: &DownmixMatrix) -> Vec<f32>
+where
+    S: Sample + IntoSample<f32> + Copy,
+{
+    let channels = buf.spec().channels.count();
+    let mut frame_buf = vec![0.0f32; channels];
+    (0..buf.frames())
+        .map(|i| {
+            for (ch, slot) in frame_buf.iter_mut().enumerate() {
+                *slot = buf.chan(ch)[i].into_sample();
+            }
+            matrix.apply(&frame_buf)
+        })
+        .collect()
+}
+
+/// Downmixes one decoded packet to mono regardless of its native sample
+/// format, dispatching to `downmix_buffer` for whichever `AudioBufferRef`
+/// variant symphonia handed back. Shared by the normalization analysis pass
+/// and the write pass so both downmix identically.
+pub(crate) fn downmix_any(decoded: AudioBufferRef<'_>, matrix: &DownmixMatrix) -> Vec<f32> {
+    match decoded {
+        AudioBufferRef::U8(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::U16(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::U24(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::U32(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::S8(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::S16(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::S24(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::S32(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::F32(buf) => downmix_buffer(buf.as_ref(), matrix),
+        AudioBufferRef::F64(buf) => downmix_buffer(buf.as_ref(), matrix),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lfe_gets_minus_ten_db_not_minus_thirteen() {
+        let channels = Channels::FRONT_LEFT
+            | Channels::FRONT_RIGHT
+            | Channels::FRONT_CENTRE
+            | Channels::LFE1
+            | Channels::REAR_LEFT
+            | Channels::REAR_RIGHT;
+        let matrix = DownmixMatrix::build(channels, true);
+
+        // LFE is the 4th recognized channel (L, R, C, LFE, RL, RR).
+        let lfe_coeff = matrix.coefficients[3];
+        assert!(
+            (lfe_coeff - LFE_ATTENUATION).abs() < 1e-6,
+            "expected LFE coefficient {}, got {}",
+            LFE_ATTENUATION,
+            lfe_coeff
+        );
+    }
+
+    #[test]
+    fn lfe_dropped_when_not_included() {
+        let channels = Channels::FRONT_LEFT
+            | Channels::FRONT_RIGHT
+            | Channels::FRONT_CENTRE
+            | Channels::LFE1
+            | Channels::REAR_LEFT
+            | Channels::REAR_RIGHT;
+        let matrix = DownmixMatrix::build(channels, false);
+
+        assert!(matrix.lfe_dropped);
+        assert_eq!(matrix.coefficients[3], 0.0);
+    }
+}
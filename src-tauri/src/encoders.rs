@@ -0,0 +1,330 @@
+use std::fs::File;
+use std::io::BufWriter;
+
+use crate::metadata::SourceTags;
+
+/// Output container/codec requested for a conversion. Mirrors the strings
+/// accepted from the frontend (`"wav"`, `"mp3"`, `"flac"`, `"vorbis"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OutputFormat {
+    #[default]
+    Wav,
+    Mp3,
+    Flac,
+    Vorbis,
+}
+
+impl OutputFormat {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Wav => "wav",
+            OutputFormat::Mp3 => "mp3",
+            OutputFormat::Flac => "flac",
+            OutputFormat::Vorbis => "ogg",
+        }
+    }
+}
+
+/// Output sample depth for the WAV encoder. The other containers are always
+/// encoded from 16-bit PCM (see `quantize_i16`), so this only steers
+/// `WavEncoder`'s `hound::WavSpec`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub(crate) enum OutputBits {
+    #[serde(rename = "16")]
+    Bits16,
+    #[serde(rename = "24")]
+    Bits24,
+    #[serde(rename = "32")]
+    Bits32Int,
+    #[serde(rename = "32f")]
+    Bits32Float,
+}
+
+impl OutputBits {
+    /// The depth to use when the caller didn't request one explicitly: the
+    /// smallest supported depth that doesn't throw away bits of the source.
+    pub(crate) fn nearest(source_bits: u32) -> Self {
+        match source_bits {
+            0..=16 => OutputBits::Bits16,
+            17..=24 => OutputBits::Bits24,
+            _ => OutputBits::Bits32Int,
+        }
+    }
+}
+
+/// Sink for the mono `f32` samples (normalized to `[-1.0, 1.0]`) produced by
+/// the decode loop. One impl per output container so `convert_to_mono`
+/// doesn't need to know the encoder details, just that it can push samples
+/// in and finalize at the end. Not `Send`: the FLAC/Vorbis encoders wrap
+/// non-Send FFI handles, and every `Box<dyn MonoEncoder>` only ever lives on
+/// the stack of a single synchronous `convert_one` call, never held across
+/// an `.await`.
+pub(crate) trait MonoEncoder {
+    fn write_samples(&mut self, samples: &[f32]) -> Result<(), String>;
+    fn finalize(self: Box<Self>) -> Result<(), String>;
+}
+
+pub(crate) struct WavEncoder {
+    writer: hound::WavWriter<BufWriter<File>>,
+    bits: OutputBits,
+}
+
+impl WavEncoder {
+    pub(crate) fn create(path: &str, sample_rate: u32, bits: OutputBits) -> Result<Self, String> {
+        let (bits_per_sample, sample_format) = match bits {
+            OutputBits::Bits16 => (16, hound::SampleFormat::Int),
+            OutputBits::Bits24 => (24, hound::SampleFormat::Int),
+            OutputBits::Bits32Int => (32, hound::SampleFormat::Int),
+            OutputBits::Bits32Float => (32, hound::SampleFormat::Float),
+        };
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        };
+        let writer = hound::WavWriter::create(path, spec)
+            .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+        Ok(Self { writer, bits })
+    }
+}
+
+impl MonoEncoder for WavEncoder {
+    fn write_samples(&mut self, samples: &[f32]) -> Result<(), String> {
+        for &sample in samples {
+            let result = match self.bits {
+                OutputBits::Bits16 => self
+                    .writer
+                    .write_sample((sample * 32767.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16),
+                OutputBits::Bits24 => self
+                    .writer
+                    .write_sample((sample * 8_388_607.0).clamp(-8_388_608.0, 8_388_607.0) as i32),
+                OutputBits::Bits32Int => self.writer.write_sample(
+                    (sample as f64 * 2_147_483_647.0).clamp(i32::MIN as f64, i32::MAX as f64) as i32,
+                ),
+                OutputBits::Bits32Float => self.writer.write_sample(sample),
+            };
+            result.map_err(|_| "Write error".to_string())?;
+        }
+        Ok(())
+    }
+
+    fn finalize(self: Box<Self>) -> Result<(), String> {
+        self.writer
+            .finalize()
+            .map_err(|_| "Finalize error".to_string())
+    }
+}
+
+pub(crate) struct Mp3Encoder {
+    encoder: mp3lame_encoder::Encoder,
+    file: File,
+}
+
+impl Mp3Encoder {
+    pub(crate) fn create(
+        path: &str,
+        sample_rate: u32,
+        bitrate_kbps: u32,
+        tags: Option<&SourceTags>,
+    ) -> Result<Self, String> {
+        use mp3lame_encoder::{Builder, Id3Tag, Quality};
+
+        let mut builder = Builder::new().ok_or("Failed to initialize MP3 encoder")?;
+        builder
+            .set_num_channels(1)
+            .map_err(|e| format!("MP3 encoder: {}", e))?;
+        builder
+            .set_sample_rate(sample_rate)
+            .map_err(|e| format!("MP3 encoder: {}", e))?;
+        builder
+            .set_brate(bitrate_for_kbps(bitrate_kbps))
+            .map_err(|e| format!("MP3 encoder: {}", e))?;
+        builder
+            .set_quality(Quality::Best)
+            .map_err(|e| format!("MP3 encoder: {}", e))?;
+        if let Some(tags) = tags {
+            // `Id3Tag` only carries these text fields - no picture slot - so
+            // cover art can't be embedded here; WAV's RIFF INFO chunk has no
+            // picture convention and `vorbis-encoder` has no comment API at
+            // all, so FLAC is the only output that carries cover art through.
+            builder.set_id3_tag(Id3Tag {
+                title: tags.title.as_deref().unwrap_or_default().as_bytes(),
+                artist: tags.artist.as_deref().unwrap_or_default().as_bytes(),
+                album: tags.album.as_deref().unwrap_or_default().as_bytes(),
+                year: b"",
+                comment: b"",
+            });
+        }
+        let encoder = builder
+            .build()
+            .map_err(|e| format!("MP3 encoder: {}", e))?;
+
+        let file = File::create(path).map_err(|e| format!("Failed to create MP3 file: {}", e))?;
+        Ok(Self { encoder, file })
+    }
+}
+
+impl MonoEncoder for Mp3Encoder {
+    fn write_samples(&mut self, samples: &[f32]) -> Result<(), String> {
+        use mp3lame_encoder::MonoPcm;
+        use std::io::Write;
+
+        let samples = quantize_i16(samples);
+        let mut out = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(samples.len()));
+        let encoded_size = self
+            .encoder
+            .encode(MonoPcm(&samples), out.spare_capacity_mut())
+            .map_err(|e| format!("MP3 encode error: {}", e))?;
+        unsafe { out.set_len(encoded_size) };
+        self.file
+            .write_all(&out)
+            .map_err(|e| format!("MP3 write error: {}", e))
+    }
+
+    fn finalize(mut self: Box<Self>) -> Result<(), String> {
+        use std::io::Write;
+
+        let mut out = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(0));
+        let flushed = self
+            .encoder
+            .flush::<mp3lame_encoder::FlushNoGap>(out.spare_capacity_mut())
+            .map_err(|e| format!("MP3 flush error: {}", e))?;
+        unsafe { out.set_len(flushed) };
+        self.file
+            .write_all(&out)
+            .map_err(|e| format!("MP3 write error: {}", e))
+    }
+}
+
+pub(crate) struct FlacEncoder {
+    encoder: flac_bound::FlacEncoder<'static>,
+}
+
+impl FlacEncoder {
+    pub(crate) fn create(path: &str, sample_rate: u32) -> Result<Self, String> {
+        let encoder = flac_bound::FlacEncoder::new()
+            .ok_or("Failed to initialize FLAC encoder")?
+            .channels(1)
+            .bits_per_sample(16)
+            .sample_rate(sample_rate)
+            .compression_level(5)
+            .init_file(&path)
+            .map_err(|e| format!("Failed to open FLAC file: {:?}", e))?;
+        Ok(Self { encoder })
+    }
+}
+
+impl MonoEncoder for FlacEncoder {
+    fn write_samples(&mut self, samples: &[f32]) -> Result<(), String> {
+        let as_i32: Vec<i32> = quantize_i16(samples).iter().map(|&s| s as i32).collect();
+        self.encoder
+            .process_interleaved(&as_i32, as_i32.len() as u32)
+            .map_err(|e| format!("FLAC encode error: {:?}", e))
+    }
+
+    fn finalize(self: Box<Self>) -> Result<(), String> {
+        match self.encoder.finish() {
+            Ok(_) => Ok(()),
+            Err(_) => Err("FLAC finalize error".to_string()),
+        }
+    }
+}
+
+pub(crate) struct VorbisEncoder {
+    encoder: vorbis_encoder::Encoder,
+    file: File,
+}
+
+impl VorbisEncoder {
+    /// `vorbis-encoder`'s public API is just `new`/`encode`/`flush` - it
+    /// doesn't expose comment-header construction, so unlike the other three
+    /// encoders, Vorbis output here carries no title/artist/album/track tags
+    /// or cover art. `tags` is accepted (and ignored) purely so
+    /// `create_encoder` can call every encoder uniformly.
+    pub(crate) fn create(path: &str, sample_rate: u32, _tags: Option<&SourceTags>) -> Result<Self, String> {
+        let encoder = vorbis_encoder::Encoder::new(1, sample_rate as u64, 0.5)
+            .map_err(|e| format!("Failed to initialize Vorbis encoder: {:?}", e))?;
+
+        let file = File::create(path).map_err(|e| format!("Failed to create Ogg file: {}", e))?;
+        Ok(Self { encoder, file })
+    }
+}
+
+impl MonoEncoder for VorbisEncoder {
+    fn write_samples(&mut self, samples: &[f32]) -> Result<(), String> {
+        use std::io::Write;
+
+        let samples = quantize_i16(samples);
+        let page = self
+            .encoder
+            .encode(&samples)
+            .map_err(|e| format!("Vorbis encode error: {:?}", e))?;
+        self.file
+            .write_all(&page)
+            .map_err(|e| format!("Vorbis write error: {}", e))
+    }
+
+    fn finalize(mut self: Box<Self>) -> Result<(), String> {
+        use std::io::Write;
+
+        let page = self
+            .encoder
+            .flush()
+            .map_err(|e| format!("Vorbis flush error: {:?}", e))?;
+        self.file
+            .write_all(&page)
+            .map_err(|e| format!("Vorbis write error: {}", e))
+    }
+}
+
+/// `mp3lame_encoder::Bitrate` is a fixed enum of the rates libmp3lame
+/// actually supports, not an arbitrary integer, so the caller's kbps figure
+/// is rounded up to the next supported one (anything above 256 becomes 320).
+fn bitrate_for_kbps(kbps: u32) -> mp3lame_encoder::Bitrate {
+    use mp3lame_encoder::Bitrate;
+    match kbps {
+        0..=8 => Bitrate::Kbps8,
+        9..=16 => Bitrate::Kbps16,
+        17..=24 => Bitrate::Kbps24,
+        25..=32 => Bitrate::Kbps32,
+        33..=40 => Bitrate::Kbps40,
+        41..=48 => Bitrate::Kbps48,
+        49..=64 => Bitrate::Kbps64,
+        65..=80 => Bitrate::Kbps80,
+        81..=96 => Bitrate::Kbps96,
+        97..=112 => Bitrate::Kbps112,
+        113..=128 => Bitrate::Kbps128,
+        129..=160 => Bitrate::Kbps160,
+        161..=192 => Bitrate::Kbps192,
+        193..=224 => Bitrate::Kbps224,
+        225..=256 => Bitrate::Kbps256,
+        _ => Bitrate::Kbps320,
+    }
+}
+
+/// MP3/FLAC/Vorbis all encode from 16-bit PCM here, regardless of the
+/// requested `OutputBits` (that knob only applies to the WAV encoder).
+fn quantize_i16(samples: &[f32]) -> Vec<i16> {
+    samples
+        .iter()
+        .map(|&s| (s * 32767.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+        .collect()
+}
+
+pub(crate) fn create_encoder(
+    format: OutputFormat,
+    path: &str,
+    sample_rate: u32,
+    mp3_bitrate_kbps: u32,
+    output_bits: OutputBits,
+    tags: Option<&SourceTags>,
+) -> Result<Box<dyn MonoEncoder>, String> {
+    match format {
+        OutputFormat::Wav => Ok(Box::new(WavEncoder::create(path, sample_rate, output_bits)?)),
+        OutputFormat::Mp3 => Ok(Box::new(Mp3Encoder::create(path, sample_rate, mp3_bitrate_kbps, tags)?)),
+        OutputFormat::Flac => Ok(Box::new(FlacEncoder::create(path, sample_rate)?)),
+        OutputFormat::Vorbis => Ok(Box::new(VorbisEncoder::create(path, sample_rate, tags)?)),
+    }
+}
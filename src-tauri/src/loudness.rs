@@ -0,0 +1,266 @@
+/// How the mono mix's level is adjusted before the final write pass.
+/// `Peak` just scales the stream so its loudest sample hits `target_dbfs`;
+/// `R128` runs a first analysis pass to measure EBU R128 integrated
+/// loudness and derives the gain needed to hit `target_lufs`.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub(crate) enum Normalization {
+    Peak { target_dbfs: f32 },
+    R128 { target_lufs: f32 },
+}
+
+pub(crate) struct LoudnessResult {
+    pub(crate) measured_loudness: f64,
+    pub(crate) applied_gain_db: f64,
+}
+
+/// One direct-form-I biquad section, used to build the two-stage
+/// K-weighting filter from ITU-R BS.1770.
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// The BS.1770 K-weighting filter: a +4dB high-shelf above ~1.65kHz
+/// ("head" filter, approximating the acoustic effect of the human head)
+/// followed by an RLB high-pass around 38Hz. Coefficients are derived at
+/// runtime since they depend on the sample rate.
+///
+/// The constants below are the reference values published for BS.1770 (as
+/// used by libebur128 etc.); kept at full precision rather than truncated to
+/// whatever clippy considers an f64's "natural" round-trip length.
+#[allow(clippy::excessive_precision)]
+fn k_weighting_filters(sample_rate: u32) -> (Biquad, Biquad) {
+    let fs = sample_rate as f64;
+
+    let f0 = 1681.9744509555319;
+    let g = 3.99984385397;
+    let q = 0.7071752369554196;
+    let k = (std::f64::consts::PI * f0 / fs).tan();
+    let vh = 10f64.powf(g / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let stage1 = Biquad {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        x1: 0.0,
+        x2: 0.0,
+        y1: 0.0,
+        y2: 0.0,
+    };
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (std::f64::consts::PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let stage2 = Biquad {
+        b0: 1.0 / a0,
+        b1: -2.0 / a0,
+        b2: 1.0 / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        x1: 0.0,
+        x2: 0.0,
+        y1: 0.0,
+        y2: 0.0,
+    };
+
+    (stage1, stage2)
+}
+
+fn loudness_from_energy(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.max(1e-12).log10()
+}
+
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// Accumulates K-weighted samples across a full first-pass decode and
+/// resolves them to EBU R128 integrated loudness (400ms blocks, 75%
+/// overlap, absolute then relative gating) on demand.
+pub(crate) struct EbuR128Analyzer {
+    stage1: Biquad,
+    stage2: Biquad,
+    weighted: Vec<f64>,
+    sample_rate: u32,
+}
+
+impl EbuR128Analyzer {
+    fn new(sample_rate: u32) -> Self {
+        let (stage1, stage2) = k_weighting_filters(sample_rate);
+        Self { stage1, stage2, weighted: Vec::new(), sample_rate }
+    }
+
+    fn push(&mut self, frames: &[f32]) {
+        self.weighted.reserve(frames.len());
+        for &x in frames {
+            self.weighted.push(self.stage2.process(self.stage1.process(x as f64)));
+        }
+    }
+
+    fn integrated_loudness(&self) -> f64 {
+        let block_len = (self.sample_rate as f64 * 0.4) as usize;
+        let hop_len = (self.sample_rate as f64 * 0.1) as usize;
+        if block_len == 0 || hop_len == 0 || self.weighted.len() < block_len {
+            let n = self.weighted.len().max(1);
+            let energy = self.weighted.iter().map(|s| s * s).sum::<f64>() / n as f64;
+            return loudness_from_energy(energy);
+        }
+
+        let mut block_energies = Vec::new();
+        let mut start = 0;
+        while start + block_len <= self.weighted.len() {
+            let energy = self.weighted[start..start + block_len].iter().map(|s| s * s).sum::<f64>() / block_len as f64;
+            block_energies.push(energy);
+            start += hop_len;
+        }
+
+        let absolute_gated: Vec<f64> = block_energies
+            .into_iter()
+            .filter(|&e| loudness_from_energy(e) > ABSOLUTE_GATE_LUFS)
+            .collect();
+        if absolute_gated.is_empty() {
+            return ABSOLUTE_GATE_LUFS;
+        }
+
+        let mean_energy = absolute_gated.iter().sum::<f64>() / absolute_gated.len() as f64;
+        let relative_gate_lufs = loudness_from_energy(mean_energy) + RELATIVE_GATE_LU;
+        let relative_gated: Vec<f64> = absolute_gated
+            .into_iter()
+            .filter(|&e| loudness_from_energy(e) > relative_gate_lufs)
+            .collect();
+        if relative_gated.is_empty() {
+            return loudness_from_energy(mean_energy);
+        }
+
+        loudness_from_energy(relative_gated.iter().sum::<f64>() / relative_gated.len() as f64)
+    }
+}
+
+/// First-pass accumulator for whichever `Normalization` mode is active.
+pub(crate) enum LoudnessAnalyzer {
+    Peak(f32),
+    R128(EbuR128Analyzer),
+}
+
+impl LoudnessAnalyzer {
+    pub(crate) fn new(mode: Normalization, sample_rate: u32) -> Self {
+        match mode {
+            Normalization::Peak { .. } => LoudnessAnalyzer::Peak(0.0),
+            Normalization::R128 { .. } => LoudnessAnalyzer::R128(EbuR128Analyzer::new(sample_rate)),
+        }
+    }
+
+    pub(crate) fn push(&mut self, frames: &[f32]) {
+        match self {
+            LoudnessAnalyzer::Peak(peak) => {
+                for &s in frames {
+                    *peak = peak.max(s.abs());
+                }
+            }
+            LoudnessAnalyzer::R128(analyzer) => analyzer.push(frames),
+        }
+    }
+
+    /// Resolves the measured loudness and the linear gain to apply during
+    /// the second pass so the output hits `mode`'s target.
+    pub(crate) fn resolve(self, mode: Normalization) -> (LoudnessResult, f32) {
+        match (self, mode) {
+            (LoudnessAnalyzer::Peak(peak), Normalization::Peak { target_dbfs }) => {
+                let peak_dbfs = if peak > 0.0 { 20.0 * peak.log10() } else { f32::NEG_INFINITY };
+                let gain_db = if peak > 0.0 { target_dbfs - peak_dbfs } else { 0.0 };
+                let gain = 10f32.powf(gain_db / 20.0);
+                (
+                    LoudnessResult { measured_loudness: peak_dbfs as f64, applied_gain_db: gain_db as f64 },
+                    gain,
+                )
+            }
+            (LoudnessAnalyzer::R128(analyzer), Normalization::R128 { target_lufs }) => {
+                let measured = analyzer.integrated_loudness();
+                let gain_db = target_lufs as f64 - measured;
+                let gain = 10f64.powf(gain_db / 20.0) as f32;
+                (LoudnessResult { measured_loudness: measured, applied_gain_db: gain_db }, gain)
+            }
+            _ => unreachable!("LoudnessAnalyzer::new and resolve are always called with the same mode"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loudness_from_energy_matches_bs1770_constant() {
+        // -0.691 dB offset at unity mean square, -3.0103 dB per halving.
+        assert!((loudness_from_energy(1.0) - (-0.691)).abs() < 1e-9);
+        assert!((loudness_from_energy(0.5) - (-3.7013)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn peak_normalization_reaches_target_dbfs() {
+        let target_dbfs = -1.0;
+        let mut analyzer = LoudnessAnalyzer::new(Normalization::Peak { target_dbfs }, 48_000);
+        analyzer.push(&[0.1, -0.5, 0.3, 0.2]);
+        let (result, gain) = analyzer.resolve(Normalization::Peak { target_dbfs });
+
+        assert!((result.measured_loudness - (20.0 * 0.5f64.log10())).abs() < 1e-6);
+        let resulting_peak_dbfs = 20.0 * (0.5 * gain as f64).log10();
+        assert!(
+            (resulting_peak_dbfs - target_dbfs as f64).abs() < 1e-4,
+            "expected peak at {} dBFS after gain, got {}",
+            target_dbfs,
+            resulting_peak_dbfs
+        );
+    }
+
+    #[test]
+    fn r128_silence_falls_back_to_absolute_gate() {
+        let sample_rate = 48_000;
+        let mut analyzer = LoudnessAnalyzer::new(Normalization::R128 { target_lufs: -16.0 }, sample_rate);
+        analyzer.push(&vec![0.0f32; sample_rate as usize]);
+        let (result, _) = analyzer.resolve(Normalization::R128 { target_lufs: -16.0 });
+        assert_eq!(result.measured_loudness, ABSOLUTE_GATE_LUFS);
+    }
+
+    #[test]
+    fn r128_full_scale_tone_is_louder_than_gate_and_gain_targets_it() {
+        let sample_rate = 48_000;
+        let target_lufs = -16.0;
+        let mut analyzer = LoudnessAnalyzer::new(Normalization::R128 { target_lufs }, sample_rate);
+
+        // A second of a 1kHz square wave is well above both the absolute and
+        // relative gates, so every block should survive gating.
+        let period = sample_rate as usize / 1000;
+        let tone: Vec<f32> = (0..sample_rate as usize)
+            .map(|i| if (i / (period / 2)) % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        analyzer.push(&tone);
+        let (result, gain) = analyzer.resolve(Normalization::R128 { target_lufs });
+
+        assert!(result.measured_loudness > ABSOLUTE_GATE_LUFS);
+        assert!((result.applied_gain_db - (target_lufs as f64 - result.measured_loudness)).abs() < 1e-9);
+        assert!((gain as f64 - 10f64.powf(result.applied_gain_db / 20.0)).abs() < 1e-6);
+    }
+}
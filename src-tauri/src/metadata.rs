@@ -0,0 +1,153 @@
+use symphonia::core::formats::FormatReader;
+use symphonia::core::meta::{StandardTagKey, Tag, Value};
+
+/// Metadata pulled from the source file's tag revision, decoded to UTF-8 and
+/// threaded into whichever output container/codec is being written.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SourceTags {
+    pub(crate) title: Option<String>,
+    pub(crate) artist: Option<String>,
+    pub(crate) album: Option<String>,
+    pub(crate) track: Option<String>,
+    pub(crate) cover_art: Option<CoverArt>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CoverArt {
+    pub(crate) mime: String,
+    pub(crate) data: Vec<u8>,
+}
+
+/// Reads the tag revision symphonia attached to the probed format. Text
+/// values symphonia already decoded (ID3v2 UTF-8/UTF-16, Vorbis comments,
+/// and its own ID3v1 Latin-1 handling) pass through untouched. The
+/// `Value::Binary` arm is a defensive fallback, not a path symphonia's own
+/// tag readers currently exercise for title/artist/album/track — it re-decodes
+/// through `encoding_rs` as Windows-1252 rather than lossily casting to UTF-8,
+/// in case a future codec/container hands back raw text bytes here.
+pub(crate) fn read_tags(format: &mut Box<dyn FormatReader>) -> SourceTags {
+    let mut tags = SourceTags::default();
+    let metadata = format.metadata();
+    let Some(revision) = metadata.current() else {
+        return tags;
+    };
+
+    for tag in revision.tags() {
+        let text = tag_text(tag);
+        match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => tags.title = text,
+            Some(StandardTagKey::Artist) => tags.artist = text,
+            Some(StandardTagKey::Album) => tags.album = text,
+            Some(StandardTagKey::TrackNumber) => tags.track = text,
+            _ => {}
+        }
+    }
+
+    if let Some(visual) = revision.visuals().first() {
+        tags.cover_art = Some(CoverArt {
+            mime: visual.media_type.clone(),
+            data: visual.data.to_vec(),
+        });
+    }
+
+    tags
+}
+
+fn tag_text(tag: &Tag) -> Option<String> {
+    match &tag.value {
+        Value::String(s) => Some(s.clone()),
+        Value::Binary(bytes) => {
+            let (decoded, _, _) = encoding_rs::WINDOWS_1252.decode(bytes);
+            Some(decoded.into_owned())
+        }
+        other => Some(other.to_string()),
+    }
+}
+
+/// Appends a RIFF `LIST/INFO` chunk (`INAM`/`IART`/`IPRD`/`ITRK`) to a WAV
+/// file hound already finalized, then patches the RIFF header's overall
+/// chunk size to account for the new bytes. hound itself has no tag-writing
+/// support, so this is a small manual append rather than a rewrite.
+pub(crate) fn append_wav_info_chunk(path: &str, tags: &SourceTags) -> Result<(), String> {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let mut info = Vec::new();
+    write_info_subchunk(&mut info, b"INAM", tags.title.as_deref());
+    write_info_subchunk(&mut info, b"IART", tags.artist.as_deref());
+    write_info_subchunk(&mut info, b"IPRD", tags.album.as_deref());
+    write_info_subchunk(&mut info, b"ITRK", tags.track.as_deref());
+
+    if info.is_empty() {
+        return Ok(());
+    }
+
+    let mut list_chunk = Vec::with_capacity(12 + info.len());
+    list_chunk.extend_from_slice(b"LIST");
+    list_chunk.extend_from_slice(&(info.len() as u32 + 4).to_le_bytes());
+    list_chunk.extend_from_slice(b"INFO");
+    list_chunk.extend_from_slice(&info);
+
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to reopen WAV for tagging: {}", e))?;
+
+    file.seek(SeekFrom::End(0))
+        .map_err(|e| format!("Failed to seek WAV for tagging: {}", e))?;
+    file.write_all(&list_chunk)
+        .map_err(|e| format!("Failed to append WAV tags: {}", e))?;
+
+    let riff_size = file
+        .stream_position()
+        .map_err(|e| format!("Failed to size WAV for tagging: {}", e))?
+        - 8;
+    file.seek(SeekFrom::Start(4))
+        .map_err(|e| format!("Failed to seek WAV header: {}", e))?;
+    file.write_all(&(riff_size as u32).to_le_bytes())
+        .map_err(|e| format!("Failed to patch WAV header: {}", e))
+}
+
+fn write_info_subchunk(out: &mut Vec<u8>, id: &[u8; 4], value: Option<&str>) {
+    let Some(value) = value else { return };
+    let mut text = value.as_bytes().to_vec();
+    text.push(0); // NUL-terminated, per the RIFF INFO convention
+    if text.len() % 2 != 0 {
+        text.push(0); // chunks are word-aligned
+    }
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    out.extend_from_slice(&text);
+}
+
+/// Writes Vorbis comments (and a `METADATA_BLOCK_PICTURE`, if cover art was
+/// found) to a FLAC file the `FlacEncoder` already finished writing.
+/// `flac_bound`'s streaming encoder doesn't expose tag blocks, so this is a
+/// post-process pass with `metaflac`, which operates directly on the
+/// file's metadata blocks.
+pub(crate) fn write_flac_tags(path: &str, tags: &SourceTags) -> Result<(), String> {
+    let mut tag = metaflac::Tag::read_from_path(path)
+        .map_err(|e| format!("Failed to read FLAC for tagging: {}", e))?;
+    let comments = tag.vorbis_comments_mut();
+
+    if let Some(title) = &tags.title {
+        comments.set_title(vec![title.clone()]);
+    }
+    if let Some(artist) = &tags.artist {
+        comments.set_artist(vec![artist.clone()]);
+    }
+    if let Some(album) = &tags.album {
+        comments.set_album(vec![album.clone()]);
+    }
+    if let Some(track) = &tags.track {
+        if let Ok(track_num) = track.parse::<u32>() {
+            comments.set_track(track_num);
+        }
+    }
+
+    if let Some(cover) = &tags.cover_art {
+        tag.add_picture(cover.mime.clone(), metaflac::block::PictureType::CoverFront, cover.data.clone());
+    }
+
+    tag.save().map_err(|e| format!("Failed to write FLAC tags: {}", e))
+}
@@ -0,0 +1,139 @@
+use rubato::{Resampler as _, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction};
+
+/// Decides whether the decoded stream needs resampling before it's written,
+/// given the optional `target_sample_rate` / `max_sample_rate` knobs exposed
+/// on `convert_to_mono` (and mirrored, read-only, by `get_audio_info`).
+pub(crate) fn target_rate_for(
+    source_rate: u32,
+    target_sample_rate: Option<u32>,
+    max_sample_rate: Option<u32>,
+) -> Option<u32> {
+    if let Some(target) = target_sample_rate {
+        return (target != source_rate).then_some(target);
+    }
+    if let Some(max) = max_sample_rate {
+        if source_rate > max {
+            return Some(max);
+        }
+    }
+    None
+}
+
+const CHUNK_FRAMES: usize = 1024;
+
+/// Buffers mono `f32` frames and pushes fixed-size blocks through a
+/// high-quality sinc resampler, accumulating the resampled output so callers
+/// can drain whole blocks as they become available.
+pub(crate) struct MonoResampler {
+    resampler: SincFixedIn<f32>,
+    input_buffer: Vec<f32>,
+    output: Vec<f32>,
+    ratio: f64,
+    // Output frames still to discard: the sinc filter's leading latency
+    // (`output_delay()`) precedes the real signal in its output
+    leading_delay: usize,
+}
+
+impl MonoResampler {
+    pub(crate) fn new(src_rate: u32, dst_rate: u32) -> Result<Self, String> {
+        let ratio = dst_rate as f64 / src_rate as f64;
+        let params = SincInterpolationParameters {
+            sinc_len: 256,
+            f_cutoff: 0.95,
+            interpolation: SincInterpolationType::Linear,
+            oversampling_factor: 256,
+            window: WindowFunction::BlackmanHarris2,
+        };
+        let resampler = SincFixedIn::<f32>::new(ratio, 2.0, params, CHUNK_FRAMES, 1)
+            .map_err(|e| format!("Failed to initialize resampler: {}", e))?;
+        let leading_delay = resampler.output_delay();
+
+        Ok(Self {
+            resampler,
+            input_buffer: Vec::new(),
+            output: Vec::new(),
+            ratio,
+            leading_delay,
+        })
+    }
+
+    /// Feeds decoded mono frames in; resampled frames ready for the encoder
+    /// accumulate internally for `drain` to collect.
+    pub(crate) fn push(&mut self, frames: &[f32]) -> Result<(), String> {
+        self.input_buffer.extend_from_slice(frames);
+        while self.input_buffer.len() >= CHUNK_FRAMES {
+            let chunk: Vec<f32> = self.input_buffer.drain(..CHUNK_FRAMES).collect();
+            let resampled = self
+                .resampler
+                .process(&[chunk], None)
+                .map_err(|e| format!("Resample error: {}", e))?;
+            let skip = self.leading_delay.min(resampled[0].len());
+            self.leading_delay -= skip;
+            self.output.extend_from_slice(&resampled[0][skip..]);
+        }
+        Ok(())
+    }
+
+    /// Drains whatever resampled output has accumulated so far.
+    pub(crate) fn drain(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Pads the trailing partial block with silence and pushes it through so
+    /// the tail of the stream isn't dropped, then trims the padding's
+    /// contribution back out. The sinc filter's `output_delay()` worth of
+    /// genuine tail content is still sitting in the resampler's internal
+    /// state at this point (it hasn't been part of any previous block's
+    /// output yet) and must be kept - only what's left after that, past
+    /// `remaining_len * ratio` real samples, is the padding's own
+    /// contribution and gets trimmed.
+    pub(crate) fn finish(mut self) -> Result<Vec<f32>, String> {
+        if !self.input_buffer.is_empty() {
+            let remaining_len = self.input_buffer.len();
+            self.input_buffer.resize(CHUNK_FRAMES, 0.0);
+            let delay = self.resampler.output_delay();
+            let resampled = self
+                .resampler
+                .process(&[self.input_buffer], None)
+                .map_err(|e| format!("Resample error: {}", e))?;
+            let trimmed_len = (((remaining_len as f64 * self.ratio).round() as usize) + delay)
+                .min(resampled[0].len());
+            let skip = self.leading_delay.min(trimmed_len);
+            self.output.extend_from_slice(&resampled[0][skip..trimmed_len]);
+        }
+        Ok(self.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs a constant-amplitude probe signal through a full push/drain/finish
+    /// cycle and returns the total number of output frames produced.
+    fn resample_all(src_rate: u32, dst_rate: u32, input_len: usize) -> usize {
+        let mut resampler = MonoResampler::new(src_rate, dst_rate).unwrap();
+        let input = vec![0.5f32; input_len];
+        resampler.push(&input).unwrap();
+        let mut total = resampler.drain().len();
+        total += resampler.finish().unwrap().len();
+        total
+    }
+
+    #[test]
+    fn resampled_length_matches_ratio_for_common_rates() {
+        let input_len = 50_000;
+        for &(src, dst) in &[(44100, 48000), (48000, 44100), (96000, 48000), (22050, 44100)] {
+            let expected = (input_len as f64 * dst as f64 / src as f64).round() as i64;
+            let got = resample_all(src, dst, input_len) as i64;
+            assert!(
+                (got - expected).abs() <= 2,
+                "{}Hz->{}Hz: expected ~{} output frames, got {}",
+                src,
+                dst,
+                expected,
+                got
+            );
+        }
+    }
+}